@@ -12,12 +12,94 @@ mod ipfs_ks {
         }
     };
 
+    use ink_prelude::vec::Vec;
+
+    #[derive(Copy, Clone, scale::Encode, scale::Decode)]
     pub enum TxOp {
         Create,
         Read,
-        Write
+        Write,
+        Remove
+    }
+
+    /// Transient net-metering state for a single transaction (one top-level
+    /// message call, or one `batch`). Never persisted to storage: it tracks
+    /// the op-count each touched file started the transaction at, so a
+    /// repeat touch can be billed at the flat "dirty" rate instead of the
+    /// escalating per-op cost, and the cost of a write that gets undone by a
+    /// same-transaction remove can be refunded.
+    struct TxMeter {
+        originals: Vec<(Hash, i32)>,
+        charged: Vec<(Hash, u128)>
     }
 
+    impl TxMeter {
+        fn new() -> Self {
+            Self {
+                originals: Vec::new(),
+                charged: Vec::new()
+            }
+        }
+
+        /// Returns the op-count `hash` had when this transaction first
+        /// touched it, recording `current` as that original if this is the
+        /// first touch.
+        fn original_or_insert(&mut self, hash: Hash, current: i32) -> i32 {
+            match self.originals.iter().find(|(h, _)| *h == hash) {
+                Some((_, original)) => *original,
+                None => {
+                    self.originals.push((hash, current));
+                    current
+                }
+            }
+        }
+
+        fn add_charge(&mut self, hash: Hash, cost: u128) {
+            match self.charged.iter_mut().find(|(h, _)| *h == hash) {
+                Some(entry) => entry.1 += cost,
+                None => self.charged.push((hash, cost))
+            }
+        }
+
+        /// Removes and returns whatever has been charged for writes to
+        /// `hash` so far this transaction, if any.
+        fn take_charge(&mut self, hash: Hash) -> Option<u128> {
+            let pos = self.charged.iter().position(|(h, _)| *h == hash)?;
+            Some(self.charged.remove(pos).1)
+        }
+    }
+
+    #[derive(Debug, Copy, Clone, PartialEq, Eq, scale::Encode, scale::Decode)]
+    pub enum Error {
+        /// The caller (or account operated on) has not called `register` yet.
+        NotRegistered,
+        /// `register` was called a second time for the same account.
+        AlreadyRegistered,
+        /// `add_file` was called with a hash that is already tracked.
+        FileExists,
+        /// The referenced file hash has no matching entry in `files`.
+        FileNotFound,
+        /// The account's balance is too low to cover the cost of the operation.
+        InsufficientBalance,
+        /// The caller does not own the file it is trying to operate on.
+        NotOwner,
+        /// The file has a lock on it: an unexpired one, for `remove_file`;
+        /// any lock not yet released, for `lock_file`.
+        FileLocked,
+        /// `unlock_file` was called on a hash with no lock recorded.
+        NotLocked,
+        /// `unlock_file` was called before the lock's unlock time.
+        LockNotExpired,
+        /// The caller is registered but lacks the ACL bit this op needs, or
+        /// called `mint` without being the contract's deployer.
+        PermissionDenied
+    }
+
+    /// Bits of the ACL permission mask stored per `(Hash, AccountId)`.
+    const PERM_READ: u8 = 0b001;
+    const PERM_WRITE: u8 = 0b010;
+    const PERM_ADMIN: u8 = 0b100;
+
     #[ink(event)]
     struct FileCreated {
         #[ink(topic)]
@@ -52,21 +134,136 @@ mod ipfs_ks {
         ts: Timestamp
     }
 
+    #[ink(event)]
+    struct FileLocked {
+        #[ink(topic)]
+        hash: Hash,
+        amount: Balance,
+        unlock_at: Timestamp,
+        #[ink(topic)]
+        ts: Timestamp
+    }
+
+    #[ink(event)]
+    struct FileUnlocked {
+        #[ink(topic)]
+        hash: Hash,
+        amount: Balance,
+        #[ink(topic)]
+        ts: Timestamp
+    }
+
+    #[ink(event)]
+    struct PermissionChanged {
+        #[ink(topic)]
+        hash: Hash,
+        #[ink(topic)]
+        account: AccountId,
+        perms: u8,
+        #[ink(topic)]
+        ts: Timestamp
+    }
+
+    #[ink(event)]
+    struct Transfer {
+        #[ink(topic)]
+        from: AccountId,
+        #[ink(topic)]
+        to: AccountId,
+        value: Balance
+    }
+
+    /// A snapshot of whatever `balances`/`files`/`locks` entries a checkpoint has
+    /// touched, recording the value each key held *before* the checkpoint
+    /// was opened (`None` meaning the key did not exist yet).
+    #[derive(scale::Encode, scale::Decode)]
+    struct ChangeSet {
+        balances: InkHashMap<AccountId, Option<Balance>>,
+        files: InkHashMap<Hash, Option<(AccountId, i32)>>,
+        locks: InkHashMap<Hash, Option<(AccountId, Balance, Timestamp)>>
+    }
+
+    impl ChangeSet {
+        fn new() -> Self {
+            Self {
+                balances: InkHashMap::new(),
+                files: InkHashMap::new(),
+                locks: InkHashMap::new()
+            }
+        }
+    }
+
     #[ink(storage)]
     struct IpfsKs {
         balances: InkHashMap<AccountId, Balance>,
         // first u32 is location, 2nd is number of ops performed on it
-        files: InkHashMap<Hash, (AccountId, i32)>
+        files: InkHashMap<Hash, (AccountId, i32)>,
+        // change-sets keyed by the stack depth they were opened at
+        checkpoints: InkHashMap<usize, ChangeSet>,
+        checkpoint_depth: usize,
+        // owner, staked amount, and unlock time for each locked file
+        locks: InkHashMap<Hash, (AccountId, Balance, Timestamp)>,
+        // permission mask granted to an account on a file it doesn't own
+        acl: InkHashMap<(Hash, AccountId), u8>,
+        total_supply: Balance,
+        // contract-owned account that collects the fees deducted in `charge`
+        treasury: AccountId,
+        // the deploying account; the only one allowed to call `mint`
+        owner: AccountId
     }
 
     impl IpfsKs {
 
         #[ink(constructor)]
-        fn new() -> Self {
+        fn new(initial_supply: Balance) -> Self {
+            let caller = Self::env().caller();
+
+            let mut balances = InkHashMap::new();
+            balances.insert(caller, initial_supply);
+
             Self {
-                balances: InkHashMap::new(),
-                files: InkHashMap::new()
+                balances: balances,
+                files: InkHashMap::new(),
+                checkpoints: InkHashMap::new(),
+                checkpoint_depth: 0,
+                locks: InkHashMap::new(),
+                acl: InkHashMap::new(),
+                total_supply: initial_supply,
+                treasury: Self::env().account_id(),
+                owner: caller
+            }
+        }
+
+        #[ink(message)]
+        fn total_supply(&self) -> Balance {
+            self.total_supply
+        }
+
+        /// Moves `value` from the caller's balance to `to`.
+        #[ink(message)]
+        fn transfer(&mut self, to: AccountId, value: Balance) -> Result<(), Error> {
+            let caller = self.env().caller();
+
+            if !self.balances.get(&caller).is_some() {
+                return Err(Error::NotRegistered)
+            }
+
+            let from_balance = self.balance_or_zero(&caller);
+            if value > from_balance {
+                return Err(Error::InsufficientBalance)
             }
+
+            let to_balance = self.balance_or_zero(&to);
+            self.balances.insert(caller, from_balance - value);
+            self.balances.insert(to, to_balance + value);
+
+            self.env().emit_event(Transfer {
+                from: caller,
+                to: to,
+                value: value
+            });
+
+            Ok(())
         }
 
         #[ink(message)]
@@ -85,43 +282,94 @@ mod ipfs_ks {
         }
 
         #[ink(message)]
-        fn register(&mut self, account: AccountId, initial_balance: Balance) -> bool {
+        fn register(&mut self, account: AccountId, initial_balance: Balance) -> Result<(), Error> {
             if self.is_user_registered(*&account) {
-                return false
+                return Err(Error::AlreadyRegistered)
+            }
+            // always create the balances entry first so the account counts
+            // as registered even if initial_balance is zero
+            self.balances.insert(account, 0);
+            if initial_balance > 0 {
+                self.mint_to(account, initial_balance);
             }
-            self.balances.insert(account, initial_balance);
-            true
+            Ok(())
         }
-        
+
+        /// Mints `value` new tokens to `to`, growing `total_supply`. Only
+        /// the contract's deployer may call this.
         #[ink(message)]
-        fn deposit(&mut self, value: Balance) -> bool {
+        fn mint(&mut self, to: AccountId, value: Balance) -> Result<(), Error> {
+            if self.env().caller() != self.owner {
+                return Err(Error::PermissionDenied)
+            }
+            self.mint_to(to, value);
+            Ok(())
+        }
+
+        fn mint_to(&mut self, to: AccountId, value: Balance) {
+            let balance = self.balance_or_zero(&to);
+            self.balances.insert(to, balance + value);
+            self.total_supply += value;
+
+            self.env().emit_event(Transfer {
+                from: AccountId::default(),
+                to: to,
+                value: value
+            });
+        }
+
+        fn burn_from(&mut self, from: AccountId, value: Balance) -> Result<(), Error> {
+            let balance = self.balance_or_zero(&from);
+            if value > balance {
+                return Err(Error::InsufficientBalance)
+            }
+            self.balances.insert(from, balance - value);
+            self.total_supply -= value;
+
+            self.env().emit_event(Transfer {
+                from: from,
+                to: AccountId::default(),
+                value: value
+            });
+
+            Ok(())
+        }
+
+        /// Tops up the caller's own balance by the amount actually
+        /// transferred with the call, minting the equivalent supply so
+        /// `total_supply` keeps matching the sum of all balances plus all
+        /// locked stakes. Being
+        /// `payable` is what stops a caller from minting themselves
+        /// tokens out of thin air: the credit is capped at whatever value
+        /// they really sent.
+        #[ink(message, payable)]
+        fn deposit(&mut self) -> Result<(), Error> {
             let caller = self.env().caller();
 
             if !self.balances.get(&caller).is_some() {
-                return false
+                return Err(Error::NotRegistered)
             }
 
-            let balance = self.balance_or_zero(&caller);
-            self.balances.insert(caller, balance + value);
+            let value = self.env().transferred_balance();
+            self.mint_to(caller, value);
 
-            true
+            Ok(())
         }
-        
+
+        /// Redeems `value` out of the caller's own balance, burning the
+        /// equivalent supply so `total_supply` keeps matching the sum of
+        /// all balances plus all locked stakes.
         #[ink(message)]
-        fn withdraw(&mut self, value: Balance) -> Balance {
+        fn withdraw(&mut self, value: Balance) -> Result<Balance, Error> {
             let caller = self.env().caller();
 
             if !self.balances.get(&caller).is_some() {
-                return 0
+                return Err(Error::NotRegistered)
             }
 
-            let balance = self.balance_or_zero(&caller);
-            if value > balance {
-                return 0
-            }
-            self.balances.insert(caller, balance - value);
+            self.burn_from(caller, value)?;
 
-            value
+            Ok(value)
         }
         
         #[ink(message)]
@@ -135,40 +383,210 @@ mod ipfs_ks {
             self.balance_or_zero(&caller)
         }
 
-        fn charge(&mut self, account: &AccountId, hash: &Hash, op: TxOp) -> bool {
+        fn charge(&mut self, account: &AccountId, hash: &Hash, op: TxOp, tx: &mut TxMeter) -> Result<(), Error> {
             let base_cost: f32 = match op {
-                TxOp::Create => 3., 
+                TxOp::Create => 3.,
                 TxOp::Read => 2.,
-                TxOp::Write => 3.
+                TxOp::Write => 3.,
+                TxOp::Remove => 0.
+            };
+            // a `Create` has no existing file entry to read yet; treat it
+            // as starting from op-count 0 rather than looking one up
+            let current = match op {
+                TxOp::Create => 0,
+                _ => self.files.get(&hash).unwrap().1
+            };
+            let original = tx.original_or_insert(*hash, current);
+            let cost = if original == current {
+                // first time this transaction has touched the file: pay the
+                // usual escalating cost
+                (base_cost + 1.07 * original as f32) as u128
+            } else {
+                // a repeat touch already paid for the escalation above, so
+                // only the flat per-op price applies again
+                base_cost as u128
             };
-            let t = self.files.get(&hash).unwrap().1 as f32;
-            let cost = (base_cost + 1.07 * t) as u128;
             let balance = self.balance_or_zero(account);
             if cost > balance {
-                return false
+                return Err(Error::InsufficientBalance)
             }
+            self.note_balance(*account);
             self.balances.insert(*account, balance - cost);
-            // the difference here should be added to the contract accounts balance
-            true
+
+            let treasury = self.treasury;
+            self.note_balance(treasury);
+            let treasury_balance = self.balance_or_zero(&treasury);
+            self.balances.insert(treasury, treasury_balance + cost);
+            self.env().emit_event(Transfer {
+                from: *account,
+                to: treasury,
+                value: cost
+            });
+
+            if let TxOp::Write = op {
+                tx.add_charge(*hash, cost);
+            }
+            Ok(())
+        }
+
+        /// Opens a new checkpoint and returns its stack depth, to be passed
+        /// back to `revert_to` or `discard` once the caller is done.
+        fn checkpoint(&mut self) -> usize {
+            let cp = self.checkpoint_depth;
+            self.checkpoints.insert(cp, ChangeSet::new());
+            self.checkpoint_depth += 1;
+            cp
+        }
+
+        /// Records the current value of `account`'s balance into the
+        /// innermost open checkpoint, unless that checkpoint already has an
+        /// original value recorded for it.
+        fn note_balance(&mut self, account: AccountId) {
+            if self.checkpoint_depth == 0 {
+                return
+            }
+            let depth = self.checkpoint_depth - 1;
+            let original = self.balances.get(&account).copied();
+            let cs = self.checkpoints.get_mut(&depth).unwrap();
+            if cs.balances.get(&account).is_none() {
+                cs.balances.insert(account, original);
+            }
         }
 
+        /// Records the current value of `hash`'s file entry into the
+        /// innermost open checkpoint, unless that checkpoint already has an
+        /// original value recorded for it.
+        fn note_file(&mut self, hash: Hash) {
+            if self.checkpoint_depth == 0 {
+                return
+            }
+            let depth = self.checkpoint_depth - 1;
+            let original = self.files.get(&hash).copied();
+            let cs = self.checkpoints.get_mut(&depth).unwrap();
+            if cs.files.get(&hash).is_none() {
+                cs.files.insert(hash, original);
+            }
+        }
+
+        /// Records the current value of `hash`'s lock entry into the
+        /// innermost open checkpoint, unless that checkpoint already has an
+        /// original value recorded for it.
+        fn note_lock(&mut self, hash: Hash) {
+            if self.checkpoint_depth == 0 {
+                return
+            }
+            let depth = self.checkpoint_depth - 1;
+            let original = self.locks.get(&hash).copied();
+            let cs = self.checkpoints.get_mut(&depth).unwrap();
+            if cs.locks.get(&hash).is_none() {
+                cs.locks.insert(hash, original);
+            }
+        }
+
+        /// Unwinds every checkpoint opened since `cp` (inclusive), restoring
+        /// each touched balance, file, and lock entry to the value it held
+        /// right before its checkpoint was opened.
+        fn revert_to(&mut self, cp: usize) {
+            while self.checkpoint_depth > cp {
+                let depth = self.checkpoint_depth - 1;
+                let cs = self.checkpoints.take(&depth).unwrap();
+                for (account, original) in cs.balances.iter() {
+                    match original {
+                        Some(balance) => { self.balances.insert(*account, *balance); },
+                        None => { self.balances.take(account); }
+                    }
+                }
+                for (hash, original) in cs.files.iter() {
+                    match original {
+                        Some(stats) => { self.files.insert(*hash, *stats); },
+                        None => { self.files.take(hash); }
+                    }
+                }
+                for (hash, original) in cs.locks.iter() {
+                    match original {
+                        Some(lock) => { self.locks.insert(*hash, *lock); },
+                        None => { self.locks.take(hash); }
+                    }
+                }
+                self.checkpoint_depth = depth;
+            }
+        }
+
+        /// Drops every checkpoint opened since `cp` (inclusive) without
+        /// undoing anything, folding their recorded originals into the
+        /// next checkpoint down so it can still roll back past this point.
+        fn discard(&mut self, cp: usize) {
+            while self.checkpoint_depth > cp {
+                let depth = self.checkpoint_depth - 1;
+                let cs = self.checkpoints.take(&depth).unwrap();
+                if depth > 0 {
+                    let parent = self.checkpoints.get_mut(&(depth - 1)).unwrap();
+                    for (account, original) in cs.balances.iter() {
+                        if parent.balances.get(account).is_none() {
+                            parent.balances.insert(*account, *original);
+                        }
+                    }
+                    for (hash, original) in cs.files.iter() {
+                        if parent.files.get(hash).is_none() {
+                            parent.files.insert(*hash, *original);
+                        }
+                    }
+                    for (hash, original) in cs.locks.iter() {
+                        if parent.locks.get(hash).is_none() {
+                            parent.locks.insert(*hash, *original);
+                        }
+                    }
+                }
+                self.checkpoint_depth = depth;
+            }
+        }
+
+        /// Applies a sequence of file operations as a single atomic unit:
+        /// either every op succeeds, or the whole batch (including its
+        /// balance debits and per-file op counters) is rolled back to
+        /// exactly how it looked before the batch started.
         #[ink(message)]
-        fn add_file(&mut self, hash: Hash) -> bool {
+        fn batch(&mut self, ops: Vec<(TxOp, Hash)>) -> Result<(), Error> {
+            let cp = self.checkpoint();
+            let mut tx = TxMeter::new();
+
+            for (op, hash) in ops {
+                let result = match op {
+                    TxOp::Create => self.add_file_with(hash, &mut tx),
+                    TxOp::Read => self.read_file_with(hash, &mut tx),
+                    TxOp::Write => self.write_file_with(hash, &mut tx),
+                    TxOp::Remove => self.remove_file_with(hash, &mut tx)
+                };
+                if let Err(e) = result {
+                    self.revert_to(cp);
+                    return Err(e)
+                }
+            }
+
+            self.discard(cp);
+            Ok(())
+        }
+
+        #[ink(message)]
+        fn add_file(&mut self, hash: Hash) -> Result<(), Error> {
+            self.add_file_with(hash, &mut TxMeter::new())
+        }
+
+        fn add_file_with(&mut self, hash: Hash, tx: &mut TxMeter) -> Result<(), Error> {
             let caller = self.env().caller();
 
             if !self.balances.get(&caller).is_some() {
-                return false
+                return Err(Error::NotRegistered)
             }
 
             if self.files.get(&hash).is_some() {
-                return false
+                return Err(Error::FileExists)
             }
 
-            if !self.charge(&caller, &hash, TxOp::Create) {
-                return false
-            }
+            self.charge(&caller, &hash, TxOp::Create, tx)?;
 
             let timestamp = self.env().block_timestamp();
+            self.note_file(hash);
             self.files.insert(hash, (caller, 0));
             self.env().emit_event(FileCreated {
                 owner: caller,
@@ -176,47 +594,95 @@ mod ipfs_ks {
                 ts: timestamp
             });
 
-            true
+            Ok(())
         }
 
         #[ink(message)]
-        fn remove_file(&mut self, hash: Hash) -> bool  {
+        fn remove_file(&mut self, hash: Hash) -> Result<(), Error> {
+            self.remove_file_with(hash, &mut TxMeter::new())
+        }
+
+        fn remove_file_with(&mut self, hash: Hash, tx: &mut TxMeter) -> Result<(), Error> {
             let caller = self.env().caller();
 
             if !self.balances.get(&caller).is_some() {
-                return false
+                return Err(Error::NotRegistered)
             }
 
             if !self.files.get(&hash).is_some() {
-                return false
+                return Err(Error::FileNotFound)
+            }
+
+            if !self.can(hash, caller, TxOp::Remove) {
+                return Err(Error::PermissionDenied)
             }
 
+            if let Some(lock) = self.locks.get(&hash).copied() {
+                if self.env().block_timestamp() < lock.2 {
+                    return Err(Error::FileLocked)
+                }
+                // the lock has already expired: release the stake back to
+                // its owner as part of tearing down the file
+                self.note_lock(hash);
+                self.locks.take(&hash);
+                let (owner, amount, _) = lock;
+                self.note_balance(owner);
+                let balance = self.balance_or_zero(&owner);
+                self.balances.insert(owner, balance + amount);
+            }
+
+            if let Some(refund) = tx.take_charge(hash) {
+                self.note_balance(caller);
+                let balance = self.balance_or_zero(&caller);
+                self.balances.insert(caller, balance + refund);
+
+                let treasury = self.treasury;
+                self.note_balance(treasury);
+                let treasury_balance = self.balance_or_zero(&treasury);
+                self.balances.insert(treasury, treasury_balance - refund);
+                self.env().emit_event(Transfer {
+                    from: treasury,
+                    to: caller,
+                    value: refund
+                });
+            }
+
+            self.note_file(hash);
+            self.files.take(&hash);
+
             let timestamp = self.env().block_timestamp();
             self.env().emit_event(FileRemoved {
                 hash: hash,
                 ts: timestamp
             });
 
-            true
+            Ok(())
         }
 
         #[ink(message)]
-        fn write_file(&mut self, hash: Hash) -> bool {
+        fn write_file(&mut self, hash: Hash) -> Result<(), Error> {
+            self.write_file_with(hash, &mut TxMeter::new())
+        }
+
+        fn write_file_with(&mut self, hash: Hash, tx: &mut TxMeter) -> Result<(), Error> {
             let caller = self.env().caller();
 
             if !self.balances.get(&caller).is_some() {
-                return false
+                return Err(Error::NotRegistered)
             }
 
             if !self.files.get(&hash).is_some() {
-                return false
+                return Err(Error::FileNotFound)
             }
 
-            if !self.charge(&caller, &hash, TxOp::Write) {
-                return false
+            if !self.can(hash, caller, TxOp::Write) {
+                return Err(Error::PermissionDenied)
             }
 
+            self.charge(&caller, &hash, TxOp::Write, tx)?;
+
             let stats = *self.files.get(&hash).unwrap();
+            self.note_file(hash);
             self.files.insert(hash, (stats.0, stats.1 + 1));
 
             let timestamp = self.env().block_timestamp();
@@ -225,26 +691,33 @@ mod ipfs_ks {
                 ts: timestamp
             });
 
-            true
+            Ok(())
         }
 
         #[ink(message)]
-        fn read_file(&mut self, hash: Hash) -> bool {
+        fn read_file(&mut self, hash: Hash) -> Result<(), Error> {
+            self.read_file_with(hash, &mut TxMeter::new())
+        }
+
+        fn read_file_with(&mut self, hash: Hash, tx: &mut TxMeter) -> Result<(), Error> {
             let caller = self.env().caller();
 
             if !self.balances.get(&caller).is_some() {
-                return false
+                return Err(Error::NotRegistered)
             }
 
             if !self.files.get(&hash).is_some() {
-                return false
+                return Err(Error::FileNotFound)
             }
 
-            if !self.charge(&caller, &hash, TxOp::Read) {
-                return false
+            if !self.can(hash, caller, TxOp::Read) {
+                return Err(Error::PermissionDenied)
             }
 
+            self.charge(&caller, &hash, TxOp::Read, tx)?;
+
             let stats = *self.files.get(&hash).unwrap();
+            self.note_file(hash);
             self.files.insert(hash, (stats.0, stats.1 + 1));
 
             let timestamp = self.env().block_timestamp();
@@ -253,7 +726,156 @@ mod ipfs_ks {
                 ts: timestamp
             });
 
-            true
+            Ok(())
+        }
+
+        /// Stakes `amount` against `hash` to guarantee it stays pinned for
+        /// `duration`, moving the stake out of the caller's balance until
+        /// it is released by `unlock_file`, or by `remove_file` once the
+        /// lock has expired.
+        #[ink(message)]
+        fn lock_file(&mut self, hash: Hash, amount: Balance, duration: Timestamp) -> Result<(), Error> {
+            let caller = self.env().caller();
+
+            if !self.balances.get(&caller).is_some() {
+                return Err(Error::NotRegistered)
+            }
+
+            if !self.files.get(&hash).is_some() {
+                return Err(Error::FileNotFound)
+            }
+
+            if self.locks.get(&hash).is_some() {
+                return Err(Error::FileLocked)
+            }
+
+            let balance = self.balance_or_zero(&caller);
+            if amount > balance {
+                return Err(Error::InsufficientBalance)
+            }
+
+            self.balances.insert(caller, balance - amount);
+
+            let timestamp = self.env().block_timestamp();
+            let unlock_at = timestamp + duration;
+            self.locks.insert(hash, (caller, amount, unlock_at));
+
+            self.env().emit_event(FileLocked {
+                hash: hash,
+                amount: amount,
+                unlock_at: unlock_at,
+                ts: timestamp
+            });
+
+            Ok(())
+        }
+
+        /// Releases a file's lock and returns its stake to the account that
+        /// locked it, once the unlock time has passed.
+        #[ink(message)]
+        fn unlock_file(&mut self, hash: Hash) -> Result<Balance, Error> {
+            let lock = match self.locks.get(&hash) {
+                Some(lock) => *lock,
+                None => return Err(Error::NotLocked)
+            };
+            let (owner, amount, unlock_at) = lock;
+
+            if self.env().block_timestamp() < unlock_at {
+                return Err(Error::LockNotExpired)
+            }
+
+            self.locks.take(&hash);
+
+            let balance = self.balance_or_zero(&owner);
+            self.balances.insert(owner, balance + amount);
+
+            let timestamp = self.env().block_timestamp();
+            self.env().emit_event(FileUnlocked {
+                hash: hash,
+                amount: amount,
+                ts: timestamp
+            });
+
+            Ok(amount)
+        }
+
+        /// A file's owner is always implicitly an admin on it; anyone else
+        /// needs the admin bit explicitly granted via `grant`.
+        fn is_admin(&self, hash: Hash, account: AccountId) -> bool {
+            match self.files.get(&hash) {
+                Some(stats) if stats.0 == account => true,
+                _ => self.acl.get(&(hash, account)).copied().unwrap_or(0) & PERM_ADMIN != 0
+            }
+        }
+
+        /// Whether `account` is allowed to perform `op` on `hash`: the
+        /// owner (and any admin) can do anything, everyone else needs the
+        /// matching permission bit. `Create` and `Remove` have no bit of
+        /// their own, so only the owner or an admin may do them.
+        fn can(&self, hash: Hash, account: AccountId, op: TxOp) -> bool {
+            if self.is_admin(hash, account) {
+                return true
+            }
+            let perms = self.acl.get(&(hash, account)).copied().unwrap_or(0);
+            match op {
+                TxOp::Read => perms & PERM_READ != 0,
+                TxOp::Write => perms & PERM_WRITE != 0,
+                TxOp::Create | TxOp::Remove => false
+            }
+        }
+
+        /// Grants `perms` on `hash` to `to`. Only the file's owner or an
+        /// existing admin may do this.
+        #[ink(message)]
+        fn grant(&mut self, hash: Hash, to: AccountId, perms: u8) -> Result<(), Error> {
+            let caller = self.env().caller();
+
+            if !self.files.get(&hash).is_some() {
+                return Err(Error::FileNotFound)
+            }
+
+            if !self.is_admin(hash, caller) {
+                return Err(Error::NotOwner)
+            }
+
+            self.acl.insert((hash, to), perms);
+
+            let timestamp = self.env().block_timestamp();
+            self.env().emit_event(PermissionChanged {
+                hash: hash,
+                account: to,
+                perms: perms,
+                ts: timestamp
+            });
+
+            Ok(())
+        }
+
+        /// Revokes whatever permissions `from` holds on `hash`. Only the
+        /// file's owner or an existing admin may do this.
+        #[ink(message)]
+        fn revoke(&mut self, hash: Hash, from: AccountId) -> Result<(), Error> {
+            let caller = self.env().caller();
+
+            if !self.files.get(&hash).is_some() {
+                return Err(Error::FileNotFound)
+            }
+
+            if !self.is_admin(hash, caller) {
+                return Err(Error::NotOwner)
+            }
+
+            self.acl.take(&(hash, from));
+
+            let timestamp = self.env().block_timestamp();
+            self.env().emit_event(PermissionChanged {
+                hash: hash,
+                account: from,
+                perms: 0,
+                ts: timestamp
+            });
+
+            Ok(())
         }
 
         fn balance_or_zero(&self, owner: &AccountId) -> Balance {